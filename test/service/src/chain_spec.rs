@@ -0,0 +1,71 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::genesis_config_presets;
+use cumulus_primitives_core::ParaId;
+use sc_chain_spec::{ChainSpecExtension, ChainSpecGroup, ChainType, GenericChainSpec};
+use serde::{Deserialize, Serialize};
+
+/// The extensions for the [`ChainSpec`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ChainSpecGroup, ChainSpecExtension)]
+#[serde(deny_unknown_fields)]
+pub struct Extensions {
+	/// The relay chain of the Parachain.
+	pub relay_chain: String,
+	/// The id of the Parachain.
+	pub para_id: u32,
+}
+
+/// The chain spec id for a given parachain id, deterministic and independent of the genesis
+/// preset. This is also the on-disk folder name the node stores its database under.
+pub fn chain_id(parachain_id: ParaId) -> String {
+	format!("cumulus_test_parachain_{}", u32::from(parachain_id))
+}
+
+/// Build the [`ChainSpec`] for the test parachain, resolving `preset` (or [`DEFAULT`] if unset)
+/// to a `RuntimeGenesisConfig` through the runtime's own
+/// [`sp_genesis_builder::GenesisBuilder::get_preset`] implementation, so the full genesis shape
+/// (parachain id, Aura authorities, balances, ...) is whatever the runtime defines for that
+/// preset, rather than a patch hand-rolled in the node binary.
+///
+/// [`DEFAULT`]: genesis_config_presets::DEFAULT
+pub fn build_chain_spec(
+	parachain_id: ParaId,
+	preset: Option<&str>,
+) -> Box<dyn sc_service::ChainSpec> {
+	let preset_id = preset.unwrap_or(genesis_config_presets::DEFAULT);
+	if !genesis_config_presets::preset_names().contains(&preset_id) {
+		tracing::warn!(
+			"Unknown genesis preset `{}`, known presets are {:?}; asking the runtime for it anyway.",
+			preset_id,
+			genesis_config_presets::preset_names(),
+		);
+	}
+
+	Box::new(
+		GenericChainSpec::<Extensions>::builder(
+			cumulus_test_service::runtime::WASM_BINARY
+				.expect("WASM binary was not built, please build it!"),
+			Extensions { relay_chain: "rococo-local".into(), para_id: parachain_id.into() },
+		)
+		.with_name("Cumulus Test Parachain")
+		.with_id(&chain_id(parachain_id))
+		.with_chain_type(ChainType::Local)
+		.with_genesis_config_preset_name(preset_id)
+		.with_protocol_id(&format!("cumulus-test-parachain-{}", u32::from(parachain_id)))
+		.build(),
+	)
+}
@@ -0,0 +1,334 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::path::PathBuf;
+
+use sc_cli::SubstrateCli;
+
+/// Sub-commands supported by the collator.
+#[derive(Debug, clap::Subcommand)]
+pub enum Commands {
+	/// Build a chain-spec.
+	BuildSpec(BuildSpecCmd),
+
+	/// Export the genesis state of the parachain.
+	ExportGenesisState(ExportGenesisStateCommand),
+
+	/// Export the genesis wasm of the parachain.
+	ExportGenesisWasm(ExportGenesisWasmCommand),
+
+	/// Validate a single block against the parachain's client.
+	CheckBlock(sc_cli::CheckBlockCmd),
+
+	/// Export blocks from the parachain's database.
+	ExportBlocks(sc_cli::ExportBlocksCmd),
+
+	/// Import blocks into the parachain's database.
+	ImportBlocks(sc_cli::ImportBlocksCmd),
+
+	/// Revert the parachain's database to a previous state.
+	Revert(sc_cli::RevertCmd),
+
+	/// Remove the whole parachain and relay chain databases.
+	PurgeChain(cumulus_client_cli::PurgeChainCmd),
+}
+
+/// Command for building a chain-spec, with support for selecting a named genesis preset.
+#[derive(Debug, clap::Parser)]
+pub struct BuildSpecCmd {
+	#[command(flatten)]
+	pub base: sc_cli::BuildSpecCmd,
+
+	/// Named runtime genesis preset to build the spec from, e.g. `default`, `high-endowment`,
+	/// or `staging`. Defaults to the runtime's default preset.
+	#[arg(long, value_name = "NAME")]
+	pub preset: Option<String>,
+}
+
+impl sc_cli::CliConfiguration for BuildSpecCmd {
+	fn shared_params(&self) -> &sc_cli::SharedParams {
+		self.base.shared_params()
+	}
+
+	fn node_name(&self) -> sc_cli::Result<String> {
+		self.base.node_name()
+	}
+}
+
+/// Command for exporting the genesis state of the parachain.
+#[derive(Debug, clap::Parser)]
+pub struct ExportGenesisStateCommand {
+	/// Output file name or stdout if unspecified.
+	#[arg(value_name = "FILE")]
+	pub output: Option<PathBuf>,
+
+	/// Id of the parachain this state is for.
+	#[arg(long, default_value = "2000")]
+	pub parachain_id: u32,
+
+	/// Write output in binary. Default is to write in hex.
+	#[arg(short, long)]
+	pub raw: bool,
+
+	/// Read the genesis header from an existing node database instead of rebuilding it from
+	/// the chain spec. Useful to confirm that the genesis head a node actually committed
+	/// matches what the spec claims.
+	#[arg(long, value_name = "BASE_PATH")]
+	pub from_db: Option<PathBuf>,
+
+	/// Named runtime genesis preset to build the state from. Ignored when `--from-db` is set.
+	#[arg(long, value_name = "NAME")]
+	pub preset: Option<String>,
+}
+
+/// Command for exporting the genesis wasm file.
+#[derive(Debug, clap::Parser)]
+pub struct ExportGenesisWasmCommand {
+	/// Output file name or stdout if unspecified.
+	#[arg(value_name = "FILE")]
+	pub output: Option<PathBuf>,
+
+	/// Id of the parachain this wasm is for.
+	#[arg(long, default_value = "2000")]
+	pub parachain_id: u32,
+
+	/// Write output in binary. Default is to write in hex.
+	#[arg(short, long)]
+	pub raw: bool,
+
+	/// Read the genesis wasm from an existing node database instead of rebuilding it from
+	/// the chain spec. Useful to confirm that the genesis wasm a node actually committed
+	/// matches what the spec claims.
+	#[arg(long, value_name = "BASE_PATH")]
+	pub from_db: Option<PathBuf>,
+
+	/// Named runtime genesis preset to build the wasm from. Ignored when `--from-db` is set.
+	#[arg(long, value_name = "NAME")]
+	pub preset: Option<String>,
+}
+
+/// Command line interface of the Cumulus test collator.
+#[derive(Debug, clap::Parser)]
+#[command(
+	propagate_version = true,
+	args_conflicts_with_subcommands = true,
+	subcommand_negates_reqs = true
+)]
+pub struct TestCollatorCli {
+	#[command(subcommand)]
+	pub subcommand: Option<Commands>,
+
+	#[command(flatten)]
+	pub run: cumulus_client_cli::RunCmd,
+
+	/// Id of the parachain this collator collates for.
+	#[arg(long, default_value = "2000")]
+	pub parachain_id: u32,
+
+	/// Disable block announcements to peers.
+	#[arg(long)]
+	pub disable_block_announcements: bool,
+
+	/// Use the null consensus that never builds any block.
+	#[arg(long)]
+	pub use_null_consensus: bool,
+
+	/// Use the lookahead Aura collator, which authors candidates ahead of the relay chain
+	/// tip and is suitable for exercising async-backing code paths. Requires a runtime that
+	/// implements `AuraApi`.
+	#[arg(long, conflicts_with = "use_null_consensus")]
+	pub use_aura_consensus: bool,
+
+	/// Number of unincluded blocks the lookahead Aura collator is allowed to author ahead of
+	/// the relay parent before it has to wait for one of them to be included. Only used with
+	/// `--use-aura-consensus`.
+	#[arg(long, default_value = "2", requires = "use_aura_consensus")]
+	pub aura_unincluded_segment_capacity: u32,
+
+	/// Run the collator in manual-seal dev mode, producing a block every `N` milliseconds and
+	/// skipping the relay chain entirely. A value of `0` seals a new block immediately on every
+	/// transaction import instead of on a timer.
+	#[arg(long, value_name = "MILLIS")]
+	pub dev_block_time: Option<u64>,
+
+	/// Relay chain arguments, passed to the embedded relay chain node, or used to derive the
+	/// relay chain configuration when connecting to a remote relay chain over RPC.
+	#[arg(raw = true)]
+	pub relaychain_args: Vec<String>,
+}
+
+impl sc_cli::SubstrateCli for TestCollatorCli {
+	fn impl_name() -> String {
+		"Cumulus Test Collator".into()
+	}
+
+	fn impl_version() -> String {
+		env!("SUBSTRATE_CLI_IMPL_VERSION").into()
+	}
+
+	fn description() -> String {
+		format!(
+			"Cumulus test collator\n\nThe command-line arguments provided first will be \
+			passed to the parachain node, while the arguments provided after -- will be passed \
+			to the relaychain node.\n\n{} [parachain-args] -- [relaychain-args]",
+			Self::executable_name()
+		)
+	}
+
+	fn author() -> String {
+		"Parity Technologies".into()
+	}
+
+	fn support_url() -> String {
+		"https://github.com/paritytech/cumulus/issues/new".into()
+	}
+
+	fn copyright_start_year() -> i32 {
+		2022
+	}
+
+	fn load_spec(&self, _: &str) -> std::result::Result<Box<dyn sc_service::ChainSpec>, String> {
+		Ok(crate::chain_spec::build_chain_spec(self.parachain_id.into(), self.preset()))
+	}
+}
+
+impl TestCollatorCli {
+	/// The named genesis preset requested by the active subcommand, if any.
+	pub fn preset(&self) -> Option<&str> {
+		match &self.subcommand {
+			Some(Commands::BuildSpec(cmd)) => cmd.preset.as_deref(),
+			Some(Commands::ExportGenesisState(cmd)) => cmd.preset.as_deref(),
+			Some(Commands::ExportGenesisWasm(cmd)) => cmd.preset.as_deref(),
+			_ => None,
+		}
+	}
+}
+
+/// The `--relaychain-args` cli arguments are passed to the RelayChainCli.
+#[derive(Debug)]
+pub struct RelayChainCli {
+	/// The actual relay chain cli object.
+	pub base: polkadot_cli::RunCmd,
+
+	/// Optional chain id that should be passed to the relay chain.
+	pub chain_id: Option<String>,
+
+	/// The base path that should be used by the relay chain.
+	pub base_path: Option<PathBuf>,
+}
+
+impl RelayChainCli {
+	/// Parse the relay chain CLI parameters using the parachain `Configuration`.
+	pub fn new<'a>(
+		para_config: &sc_service::Configuration,
+		relay_chain_args: impl Iterator<Item = &'a String>,
+	) -> Self {
+		let base_path = para_config.base_path.path().join("polkadot");
+
+		Self {
+			base_path: Some(base_path),
+			chain_id: None,
+			base: clap::Parser::parse_from(relay_chain_args),
+		}
+	}
+}
+
+impl sc_cli::SubstrateCli for RelayChainCli {
+	fn impl_name() -> String {
+		"Cumulus Test Collator".into()
+	}
+
+	fn impl_version() -> String {
+		env!("SUBSTRATE_CLI_IMPL_VERSION").into()
+	}
+
+	fn description() -> String {
+		"Cumulus test collator".into()
+	}
+
+	fn author() -> String {
+		"Parity Technologies".into()
+	}
+
+	fn support_url() -> String {
+		"https://github.com/paritytech/cumulus/issues/new".into()
+	}
+
+	fn copyright_start_year() -> i32 {
+		2022
+	}
+
+	fn load_spec(&self, id: &str) -> std::result::Result<Box<dyn sc_service::ChainSpec>, String> {
+		polkadot_cli::Cli::from_iter([polkadot_cli::Cli::executable_name()].iter()).load_spec(id)
+	}
+
+	fn native_runtime_version(
+		chain_spec: &Box<dyn sc_service::ChainSpec>,
+	) -> &'static sp_version::RuntimeVersion {
+		polkadot_cli::Cli::native_runtime_version(chain_spec)
+	}
+}
+
+impl sc_cli::CliConfiguration<Self> for RelayChainCli {
+	fn shared_params(&self) -> &sc_cli::SharedParams {
+		self.base.base.shared_params()
+	}
+
+	fn import_params(&self) -> Option<&sc_cli::ImportParams> {
+		self.base.base.import_params()
+	}
+
+	fn network_params(&self) -> Option<&sc_cli::NetworkParams> {
+		self.base.base.network_params()
+	}
+
+	fn keystore_params(&self) -> Option<&sc_cli::KeystoreParams> {
+		self.base.base.keystore_params()
+	}
+
+	fn base_path(&self) -> sc_cli::Result<Option<sc_service::BasePath>> {
+		Ok(self
+			.shared_params()
+			.base_path()?
+			.or_else(|| self.base_path.clone().map(Into::into)))
+	}
+}
+
+impl RelayChainCli {
+	/// Build a bare-bones relay chain `Configuration` for the case where the collator connects
+	/// to an already-running relay chain node over RPC instead of spawning an embedded one.
+	///
+	/// This still parses `relaychain_args` the same way the embedded-node path does, since
+	/// `--relaychain-args` can carry things like `--chain`/`--rpc-port` that affect how the
+	/// light client configuration is derived even though no full relay chain node is spawned.
+	pub fn build_light_client_config(
+		para_config: &sc_service::Configuration,
+		relaychain_args: &[String],
+		tokio_handle: tokio::runtime::Handle,
+	) -> sc_cli::Result<sc_service::Configuration> {
+		let polkadot_cli = RelayChainCli::new(
+			para_config,
+			[RelayChainCli::executable_name().to_string()].iter().chain(relaychain_args.iter()),
+		);
+
+		let mut config =
+			SubstrateCli::create_configuration(&polkadot_cli, &polkadot_cli, tokio_handle)?;
+		config.network.listen_addresses.clear();
+		config.role = sc_service::Role::Light;
+
+		Ok(config)
+	}
+}
@@ -0,0 +1,37 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Named genesis presets for the test parachain.
+//!
+//! The presets themselves (`default`, `high-endowment`, `staging`) are built by the runtime via
+//! [`sp_genesis_builder::GenesisBuilder::get_preset`] and resolved through
+//! [`sc_chain_spec::ChainSpecBuilder::with_genesis_config_preset_name`] — this module only keeps
+//! the known names, which the chain spec builder uses to warn about an unknown preset name (the
+//! name is still passed on to the runtime); it does not construct genesis state itself.
+
+/// The preset used when the caller doesn't request one explicitly.
+pub const DEFAULT: &str = "default";
+
+/// A preset with a much higher endowment, useful for load and fee-exhaustion tests.
+pub const HIGH_ENDOWMENT: &str = "high-endowment";
+
+/// The preset used for long-running staging/testnet style deployments.
+pub const STAGING: &str = "staging";
+
+/// All genesis presets the runtime is expected to expose, in the order they should be listed.
+pub fn preset_names() -> Vec<&'static str> {
+	vec![DEFAULT, HIGH_ENDOWMENT, STAGING]
+}
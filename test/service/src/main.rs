@@ -13,18 +13,24 @@
 
 // You should have received a copy of the GNU General Public License
 // along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+mod chain_spec;
 mod cli;
+mod genesis_config_presets;
 
 use clap::Parser;
 use cli::{Commands, RelayChainCli, TestCollatorCli};
 use cumulus_client_service::genesis::generate_genesis_block;
 use cumulus_primitives_core::{relay_chain::v2::CollatorPair, ParaId};
+use cumulus_primitives_parachain_inherent::MockValidationDataInherentDataProvider;
 use cumulus_test_service::AnnounceBlockFn;
+use futures::{Stream, StreamExt};
 use polkadot_service::runtime_traits::AccountIdConversion;
 use sc_cli::{CliConfiguration, SubstrateCli};
+use sc_consensus_manual_seal::{run_manual_seal, EngineCommand, ManualSealParams};
+use sc_transaction_pool_api::TransactionPool;
 use sp_core::{hexdisplay::HexDisplay, Encode, Pair};
 use sp_runtime::traits::Block;
-use std::{io::Write, sync::Arc};
+use std::{io::Write, sync::Arc, time::Duration};
 
 fn extract_genesis_wasm(chain_spec: &Box<dyn sc_service::ChainSpec>) -> Result<Vec<u8>, String> {
 	let mut storage = chain_spec.build_storage()?;
@@ -35,6 +41,70 @@ fn extract_genesis_wasm(chain_spec: &Box<dyn sc_service::ChainSpec>) -> Result<V
 		.ok_or_else(|| "Could not find wasm file in genesis state!".into())
 }
 
+/// Open an existing node database and return its backend, without starting the node itself.
+///
+/// Used by `--from-db` on the genesis export subcommands to read back whatever a node actually
+/// committed at height zero, rather than recomputing it from the chain spec. The on-disk chain
+/// folder is derived from the same deterministic chain id the running node would have used
+/// (keyed on `--parachain-id`), so inspecting a node started with a non-default parachain id
+/// looks in the right place instead of silently reading an unrelated database.
+fn open_db_backend(
+	base_path: &std::path::Path,
+	parachain_id: ParaId,
+) -> Result<Arc<sc_client_db::Backend<parachains_common::Block>>, String> {
+	let db_settings = sc_client_db::DatabaseSettings {
+		trie_cache_maximum_size: None,
+		state_pruning: None,
+		source: sc_client_db::DatabaseSource::RocksDb {
+			path: base_path
+				.join("chains")
+				.join(chain_spec::chain_id(parachain_id))
+				.join("db")
+				.join("full"),
+			cache_size: 128,
+		},
+		blocks_pruning: sc_client_db::BlocksPruning::KeepAll,
+	};
+
+	sc_client_db::Backend::new(db_settings, 0).map_err(|e| format!("{}", e))
+}
+
+/// Read the SCALE-encoded genesis header out of an existing node database.
+fn genesis_header_from_db(
+	base_path: &std::path::Path,
+	parachain_id: ParaId,
+) -> Result<Vec<u8>, String> {
+	use sc_client_api::backend::Backend as _;
+
+	let backend = open_db_backend(base_path, parachain_id)?;
+	let genesis_hash = backend.blockchain().info().genesis_hash;
+	let header = sc_client_api::blockchain::Backend::<parachains_common::Block>::header(
+		backend.blockchain(),
+		genesis_hash,
+	)
+	.map_err(|e| format!("{}", e))?
+	.ok_or_else(|| "Genesis header not found in the database".to_string())?;
+
+	Ok(header.encode())
+}
+
+/// Read the genesis wasm code out of an existing node database.
+fn genesis_wasm_from_db(
+	base_path: &std::path::Path,
+	parachain_id: ParaId,
+) -> Result<Vec<u8>, String> {
+	use sc_client_api::backend::{Backend as _, StateBackend as _};
+
+	let backend = open_db_backend(base_path, parachain_id)?;
+	let genesis_hash = backend.blockchain().info().genesis_hash;
+	let state = backend.state_at(genesis_hash).map_err(|e| format!("{}", e))?;
+
+	state
+		.storage(sp_core::storage::well_known_keys::CODE)
+		.map_err(|e| format!("{}", e))?
+		.ok_or_else(|| "Could not find wasm code in genesis state!".to_string())
+}
+
 pub fn wrap_announce_block() -> Box<dyn FnOnce(AnnounceBlockFn) -> AnnounceBlockFn> {
 	tracing::info!("Block announcements disabled.");
 	Box::new(|_| {
@@ -43,13 +113,226 @@ pub fn wrap_announce_block() -> Box<dyn FnOnce(AnnounceBlockFn) -> AnnounceBlock
 	})
 }
 
+/// Build a stream that yields a sealing command every `block_time_ms` milliseconds, or
+/// immediately when a transaction enters the ready queue, when `block_time_ms` is `0`.
+fn dev_block_authorship_stream(
+	block_time_ms: u64,
+	pool_import_notifications: impl Stream<Item = ()> + Send + Unpin + 'static,
+) -> impl Stream<Item = EngineCommand<sp_core::H256>> + Send + Unpin + 'static {
+	if block_time_ms == 0 {
+		Box::pin(pool_import_notifications.map(|_| EngineCommand::SealNewBlock {
+			create_empty: false,
+			finalize: true,
+			parent_hash: None,
+			sender: None,
+		})) as std::pin::Pin<Box<dyn Stream<Item = _> + Send>>
+	} else {
+		Box::pin(
+			futures_timer::Delay::new(Duration::from_millis(block_time_ms))
+				.map(move |_| ())
+				.into_stream()
+				.chain(futures::stream::unfold((), move |_| async move {
+					futures_timer::Delay::new(Duration::from_millis(block_time_ms)).await;
+					Some(((), ()))
+				}))
+				.map(|_| EngineCommand::SealNewBlock {
+					create_empty: true,
+					finalize: true,
+					parent_hash: None,
+					sender: None,
+				}),
+		)
+	}
+}
+
+/// Run the collator in manual-seal dev mode: no relay chain, just a local parachain that seals
+/// its own blocks on a timer (or on transaction import, when `block_time_ms` is `0`).
+async fn start_dev_node(
+	config: sc_service::Configuration,
+	parachain_id: ParaId,
+	block_time_ms: u64,
+) -> sc_service::error::Result<sc_service::TaskManager> {
+	let cumulus_test_service::PartialComponents {
+		client,
+		backend,
+		mut task_manager,
+		import_queue,
+		keystore_container,
+		select_chain: _,
+		transaction_pool,
+		other: (_block_import, _, _),
+	} = cumulus_test_service::new_partial(&config)?;
+
+	let pool_import_notifications = transaction_pool.import_notification_stream().map(|_| ());
+	let commands_stream = dev_block_authorship_stream(block_time_ms, pool_import_notifications);
+
+	let client_for_cidp = client.clone();
+	let relay_chain_block_number = Arc::new(std::sync::atomic::AtomicU32::new(1));
+
+	task_manager.spawn_essential_handle().spawn_blocking(
+		"manual-seal",
+		None,
+		run_manual_seal(ManualSealParams {
+			block_import: client_for_cidp.clone(),
+			env: sc_basic_authorship::ProposerFactory::new(
+				task_manager.spawn_handle(),
+				client.clone(),
+				transaction_pool.clone(),
+				None,
+				None,
+			),
+			client: client.clone(),
+			pool: transaction_pool.clone(),
+			commands_stream,
+			select_chain: sc_consensus::LongestChain::new(backend.clone()),
+			consensus_data_provider: None,
+			create_inherent_data_providers: move |_, _| {
+				let relay_chain_block_number = relay_chain_block_number.clone();
+				async move {
+					let relay_number = relay_chain_block_number
+						.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+					let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+					let mocked_parachain = MockValidationDataInherentDataProvider {
+						current_para_block: 0,
+						relay_offset: relay_number,
+						relay_blocks_per_para_block: 1,
+						raw_downward_messages: vec![],
+						raw_horizontal_messages: vec![],
+						para_id: parachain_id,
+						xcm_config: Default::default(),
+					};
+
+					Ok((timestamp, mocked_parachain))
+				}
+			},
+		}),
+	);
+
+	tracing::info!("Manual seal dev mode started (parachain id: {:?}).", parachain_id);
+
+	let _ = import_queue;
+	let _ = keystore_container;
+
+	Ok(task_manager)
+}
+
+/// Run the collator with the lookahead Aura collator, authoring candidates ahead of the relay
+/// parent (up to `unincluded_segment_capacity` unincluded blocks) and feeding them through the
+/// collation proposer, so async-backing code paths can be exercised.
+async fn start_aura_lookahead_node(
+	parachain_config: sc_service::Configuration,
+	polkadot_config: sc_service::Configuration,
+	collator_options: cumulus_client_cli::CollatorOptions,
+	parachain_id: ParaId,
+	collator_key: sp_core::sr25519::Pair,
+	unincluded_segment_capacity: u32,
+) -> sc_service::error::Result<sc_service::TaskManager> {
+	let cumulus_test_service::PartialComponents {
+		client,
+		backend,
+		mut task_manager,
+		import_queue,
+		keystore_container,
+		select_chain: _,
+		transaction_pool,
+		other: (block_import, _, _),
+	} = cumulus_test_service::new_partial(&parachain_config)?;
+
+	let (relay_chain_interface, _) = cumulus_client_service::build_relay_chain_interface(
+		polkadot_config,
+		&parachain_config,
+		None,
+		&mut task_manager,
+		collator_options,
+		None,
+	)
+	.await
+	.map_err(|e| format!("Could not build the relay chain interface: {}", e))?;
+
+	let overseer_handle = relay_chain_interface
+		.overseer_handle()
+		.map_err(|e| format!("Failed to get the relay chain overseer handle: {}", e))?;
+
+	let proposer_factory = sc_basic_authorship::ProposerFactory::new(
+		task_manager.spawn_handle(),
+		client.clone(),
+		transaction_pool.clone(),
+		None,
+		None,
+	);
+	let proposer = cumulus_client_consensus_proposer::Proposer::new(proposer_factory);
+
+	let collator_service = cumulus_client_collator::service::CollatorService::new(
+		client.clone(),
+		Arc::new(task_manager.spawn_handle()),
+		Arc::new(|_, _| {}),
+		client.clone(),
+	);
+
+	let code_hash_provider = {
+		let client = client.clone();
+		move |block_hash| client.code_at(block_hash).ok().map(|c| sp_core::blake2_256(&c).into())
+	};
+
+	let slot_duration = cumulus_client_consensus_aura::slot_duration(&*client)
+		.map_err(|e| format!("Failed to fetch slot duration from the runtime: {}", e))?;
+
+	let params = cumulus_client_consensus_aura::collators::lookahead::Params {
+		create_inherent_data_providers: move |_, ()| async move {
+			Ok(sp_timestamp::InherentDataProvider::from_system_time())
+		},
+		block_import,
+		para_client: client.clone(),
+		para_backend: backend,
+		relay_client: relay_chain_interface,
+		code_hash_provider,
+		keystore: keystore_container.keystore(),
+		collator_key,
+		para_id: parachain_id,
+		overseer_handle,
+		slot_duration,
+		proposer,
+		collator_service,
+		authoring_duration: Duration::from_millis(1500),
+		reinitialize: false,
+		unincluded_segment_capacity,
+	};
+
+	task_manager.spawn_essential_handle().spawn(
+		"aura-lookahead-collator",
+		None,
+		cumulus_client_consensus_aura::collators::lookahead::run::<
+			parachains_common::Block,
+			sp_consensus_aura::sr25519::AuthorityPair,
+			_,
+			_,
+			_,
+			_,
+			_,
+			_,
+			_,
+			_,
+		>(params),
+	);
+
+	tracing::info!(
+		"Lookahead Aura collator started (parachain id: {:?}, unincluded segment capacity: {}).",
+		parachain_id,
+		unincluded_segment_capacity
+	);
+
+	let _ = import_queue;
+
+	Ok(task_manager)
+}
+
 fn main() -> Result<(), sc_cli::Error> {
 	let cli = TestCollatorCli::parse();
 
 	match &cli.subcommand {
 		Some(Commands::BuildSpec(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
-			runner.sync_run(|config| cmd.run(config.chain_spec, config.network))
+			runner.sync_run(|config| cmd.base.run(config.chain_spec, config.network))
 		},
 		Some(Commands::ExportGenesisState(params)) => {
 			let mut builder = sc_cli::LoggerBuilder::new("");
@@ -57,15 +340,18 @@ fn main() -> Result<(), sc_cli::Error> {
 			let _ = builder.init();
 
 			let parachain_id = ParaId::from(params.parachain_id);
-			let spec = Box::new(cumulus_test_service::get_chain_spec(parachain_id)) as Box<_>;
-			let state_version = cumulus_test_service::runtime::VERSION.state_version();
-
-			let block: parachains_common::Block = generate_genesis_block(&spec, state_version)?;
-			let raw_header = block.header().encode();
+			let raw_header = if let Some(base_path) = &params.from_db {
+				genesis_header_from_db(base_path, parachain_id)?
+			} else {
+				let spec = chain_spec::build_chain_spec(parachain_id, params.preset.as_deref());
+				let state_version = cumulus_test_service::runtime::VERSION.state_version();
+				let block: parachains_common::Block = generate_genesis_block(&spec, state_version)?;
+				block.header().encode()
+			};
 			let output_buf = if params.raw {
-				raw_header
+				raw_header.clone()
 			} else {
-				format!("0x{:?}", HexDisplay::from(&block.header().encode())).into_bytes()
+				format!("0x{:?}", HexDisplay::from(&raw_header)).into_bytes()
 			};
 
 			if let Some(output) = &params.output {
@@ -82,8 +368,12 @@ fn main() -> Result<(), sc_cli::Error> {
 			let _ = builder.init();
 
 			let parachain_id = ParaId::from(params.parachain_id);
-			let spec = Box::new(cumulus_test_service::get_chain_spec(parachain_id)) as Box<_>;
-			let raw_wasm_blob = extract_genesis_wasm(&spec)?;
+			let raw_wasm_blob = if let Some(base_path) = &params.from_db {
+				genesis_wasm_from_db(base_path, parachain_id)?
+			} else {
+				let spec = chain_spec::build_chain_spec(parachain_id, params.preset.as_deref());
+				extract_genesis_wasm(&spec)?
+			};
 			let output_buf = if params.raw {
 				raw_wasm_blob
 			} else {
@@ -98,6 +388,57 @@ fn main() -> Result<(), sc_cli::Error> {
 
 			Ok(())
 		},
+		Some(Commands::CheckBlock(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let cumulus_test_service::PartialComponents { client, task_manager, import_queue, .. } =
+					cumulus_test_service::new_partial(&config)?;
+				Ok((cmd.run(client, import_queue), task_manager))
+			})
+		},
+		Some(Commands::ExportBlocks(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let cumulus_test_service::PartialComponents { client, task_manager, .. } =
+					cumulus_test_service::new_partial(&config)?;
+				Ok((cmd.run(client, config.database), task_manager))
+			})
+		},
+		Some(Commands::ImportBlocks(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let cumulus_test_service::PartialComponents { client, task_manager, import_queue, .. } =
+					cumulus_test_service::new_partial(&config)?;
+				Ok((cmd.run(client, import_queue), task_manager))
+			})
+		},
+		Some(Commands::Revert(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let cumulus_test_service::PartialComponents { client, backend, task_manager, .. } =
+					cumulus_test_service::new_partial(&config)?;
+				Ok((cmd.run(client, backend, None), task_manager))
+			})
+		},
+		Some(Commands::PurgeChain(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let polkadot_cli = RelayChainCli::new(
+					&config,
+					[RelayChainCli::executable_name().to_string()]
+						.iter()
+						.chain(cli.relaychain_args.iter()),
+				);
+				let polkadot_config = SubstrateCli::create_configuration(
+					&polkadot_cli,
+					&polkadot_cli,
+					config.tokio_handle.clone(),
+				)
+				.map_err(|err| format!("Relay chain argument error: {}", err))?;
+
+				cmd.run(config, polkadot_config)
+			})
+		},
 		None => {
 			let mut builder = sc_cli::LoggerBuilder::new("");
 			builder.with_colors(true);
@@ -113,12 +454,17 @@ fn main() -> Result<(), sc_cli::Error> {
 				.expect("Should be able to generate config");
 
 			let parachain_id = ParaId::from(cli.parachain_id);
-			let polkadot_cli = RelayChainCli::new(
-				&config,
-				[RelayChainCli::executable_name().to_string()]
-					.iter()
-					.chain(cli.relaychain_args.iter()),
-			);
+
+			if let Some(block_time_ms) = cli.dev_block_time {
+				tracing::info!("Dev block time: {}ms, running without a relay chain.", block_time_ms);
+				let task_manager = tokio_runtime
+					.block_on(start_dev_node(config, parachain_id, block_time_ms))
+					.expect("could not start dev node");
+				tokio_runtime
+					.block_on(task_manager.future())
+					.expect("Could not run dev node to completion");
+				return Ok(());
+			}
 
 			let parachain_account =
 				AccountIdConversion::<polkadot_primitives::v2::AccountId>::into_account_truncating(
@@ -134,9 +480,27 @@ fn main() -> Result<(), sc_cli::Error> {
 			let genesis_state = format!("0x{:?}", HexDisplay::from(&block.header().encode()));
 
 			let tokio_handle = config.tokio_handle.clone();
-			let polkadot_config =
+			let polkadot_config = if let Some(relay_chain_rpc_urls) =
+				(!collator_options.relay_chain_rpc_urls.is_empty())
+					.then(|| collator_options.relay_chain_rpc_urls.clone())
+			{
+				tracing::info!(
+					"Connecting to remote relay chain(s) at {:?}, skipping the embedded relay chain node.",
+					relay_chain_rpc_urls
+				);
+				RelayChainCli::build_light_client_config(&config, &cli.relaychain_args, tokio_handle)
+					.map_err(|err| format!("Relay chain RPC configuration error: {}", err))?
+			} else {
+				let polkadot_cli = RelayChainCli::new(
+					&config,
+					[RelayChainCli::executable_name().to_string()]
+						.iter()
+						.chain(cli.relaychain_args.iter()),
+				);
+
 				SubstrateCli::create_configuration(&polkadot_cli, &polkadot_cli, tokio_handle)
-					.map_err(|err| format!("Relay chain argument error: {}", err))?;
+					.map_err(|err| format!("Relay chain argument error: {}", err))?
+			};
 
 			tracing::info!("Parachain id: {:?}", parachain_id);
 			tracing::info!("Parachain Account: {}", parachain_account);
@@ -146,16 +510,50 @@ fn main() -> Result<(), sc_cli::Error> {
 				if config.role.is_authority() { "yes" } else { "no" }
 			);
 
-			let collator_key = Some(CollatorPair::generate().0);
+			let collator_key = CollatorPair::generate().0;
+
+			if cli.use_aura_consensus {
+				let supports_aura_api = cumulus_test_service::runtime::VERSION.apis.iter().any(
+					|(id, _)| {
+						id == &<dyn sp_consensus_aura::AuraApi<
+							parachains_common::Block,
+							sp_consensus_aura::sr25519::AuthorityId,
+						> as sp_api::RuntimeApiInfo>::ID
+					},
+				);
+				if !supports_aura_api {
+					return Err(
+						"Parachain runtime does not implement `AuraApi`, cannot use --use-aura-consensus"
+							.to_string()
+							.into(),
+					);
+				}
+
+				tracing::info!("Using lookahead Aura consensus.");
+				let task_manager = tokio_runtime
+					.block_on(start_aura_lookahead_node(
+						config,
+						polkadot_config,
+						collator_options,
+						parachain_id,
+						collator_key,
+						cli.aura_unincluded_segment_capacity,
+					))
+					.expect("could not start the lookahead Aura collator");
+				tokio_runtime
+					.block_on(task_manager.future())
+					.expect("Could not run the lookahead Aura collator to completion");
+				return Ok(());
+			}
 
-			let consensus = cli
-				.use_null_consensus
-				.then(|| {
-					tracing::info!("Using null consensus.");
-					cumulus_test_service::Consensus::Null
-				})
-				.unwrap_or(cumulus_test_service::Consensus::RelayChain);
+			let consensus = if cli.use_null_consensus {
+				tracing::info!("Using null consensus.");
+				cumulus_test_service::Consensus::Null
+			} else {
+				cumulus_test_service::Consensus::RelayChain
+			};
 
+			let collator_key = Some(collator_key);
 			let (mut task_manager, _, _, _, _) = tokio_runtime
 				.block_on(cumulus_test_service::start_node_impl(
 					config,